@@ -0,0 +1,171 @@
+// src/export.rs
+use crate::rules::LogCategory;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Default per-file byte budget before a category's log file rotates,
+/// matching log_listener's `DEFAULT_FILE_CAPACITY` convention.
+pub const DEFAULT_FILE_CAPACITY: u64 = 10 * 1024 * 1024;
+
+/// Appends lines to `{dir}/{base_name}`, rolling the file over to
+/// `{base_name}.1`, `{base_name}.2`, ... once it would exceed `capacity`
+/// bytes, so a long-running `--follow --save` session doesn't grow one file
+/// forever.
+struct RotatingWriter {
+    dir: PathBuf,
+    base_name: &'static str,
+    capacity: u64,
+    file: File,
+    written: u64,
+}
+
+impl RotatingWriter {
+    fn new(dir: &Path, base_name: &'static str, capacity: u64) -> io::Result<Self> {
+        let path = dir.join(base_name);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(RotatingWriter {
+            dir: dir.to_path_buf(),
+            base_name,
+            capacity,
+            file,
+            written,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let line_len = line.len() as u64 + 1; // +1 for the trailing newline
+        if self.written > 0 && self.written + line_len > self.capacity {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()?;
+        self.written += line_len;
+        Ok(())
+    }
+
+    /// Shifts `base_name.N` to `base_name.(N+1)` for every existing rollover
+    /// file (highest index first, so none are clobbered), moves the active
+    /// file to `base_name.1`, and starts a fresh active file.
+    fn rotate(&mut self) -> io::Result<()> {
+        let mut index = 1;
+        while self.dir.join(format!("{}.{}", self.base_name, index)).exists() {
+            index += 1;
+        }
+        while index > 1 {
+            let from = self.dir.join(format!("{}.{}", self.base_name, index - 1));
+            let to = self.dir.join(format!("{}.{}", self.base_name, index));
+            fs::rename(from, to)?;
+            index -= 1;
+        }
+
+        let active_path = self.dir.join(self.base_name);
+        let rolled_path = self.dir.join(format!("{}.1", self.base_name));
+        fs::rename(&active_path, &rolled_path)?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&active_path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// One size-rotated log file per severity, written in the plain (uncolored)
+/// format so the archives stay grep-able.
+pub struct ExportWriters {
+    critical: RotatingWriter,
+    error: RotatingWriter,
+    warning: RotatingWriter,
+    info: RotatingWriter,
+}
+
+impl ExportWriters {
+    /// Creates `dir` if needed and opens (or resumes) a rotating writer per
+    /// category inside it.
+    pub fn open(dir: &Path, capacity: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(ExportWriters {
+            critical: RotatingWriter::new(dir, "critical.log", capacity)?,
+            error: RotatingWriter::new(dir, "error.log", capacity)?,
+            warning: RotatingWriter::new(dir, "warning.log", capacity)?,
+            info: RotatingWriter::new(dir, "info.log", capacity)?,
+        })
+    }
+
+    /// Appends `plain_text` to the file for `category`, flushing immediately
+    /// so the archive stays current alongside a live `--follow` session.
+    pub fn write(&mut self, category: LogCategory, plain_text: &str) -> io::Result<()> {
+        let writer = match category {
+            LogCategory::Critical => &mut self.critical,
+            LogCategory::Error => &mut self.error,
+            LogCategory::Warning => &mut self.warning,
+            LogCategory::Info => &mut self.info,
+        };
+        writer.write_line(plain_text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn read(dir: &Path, name: &str) -> String {
+        fs::read_to_string(dir.join(name)).unwrap_or_default()
+    }
+
+    #[test]
+    fn rotates_through_several_rollover_files_in_order() {
+        let dir = tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "log", 5).unwrap();
+
+        writer.write_line("a").unwrap();
+        writer.write_line("b").unwrap();
+        // "a\nb\n" is 4 bytes; appending "c\n" would exceed the 5 byte budget.
+        writer.write_line("c").unwrap();
+        writer.write_line("d").unwrap();
+        // "c\nd\n" is 4 bytes; appending "e\n" would exceed the budget again.
+        writer.write_line("e").unwrap();
+
+        assert_eq!(read(dir.path(), "log"), "e\n");
+        assert_eq!(read(dir.path(), "log.1"), "c\nd\n");
+        assert_eq!(read(dir.path(), "log.2"), "a\nb\n");
+    }
+
+    #[test]
+    fn resuming_a_file_already_over_capacity_rotates_on_the_next_write() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("log");
+        fs::write(&path, "existing content well past the budget\n").unwrap();
+
+        let mut writer = RotatingWriter::new(dir.path(), "log", 5).unwrap();
+        writer.write_line("fresh").unwrap();
+
+        assert_eq!(read(dir.path(), "log"), "fresh\n");
+        assert_eq!(read(dir.path(), "log.1"), "existing content well past the budget\n");
+    }
+
+    #[test]
+    fn categories_rotate_independently_and_do_not_cross_contaminate() {
+        let dir = tempdir().unwrap();
+        let mut writers = ExportWriters::open(dir.path(), 5).unwrap();
+
+        writers.write(LogCategory::Critical, "a").unwrap();
+        writers.write(LogCategory::Info, "x").unwrap();
+        writers.write(LogCategory::Critical, "b").unwrap();
+        // Critical's "a\nb\n" is 4 bytes; a third line rotates only critical.
+        writers.write(LogCategory::Critical, "c").unwrap();
+        writers.write(LogCategory::Info, "y").unwrap();
+
+        assert_eq!(read(dir.path(), "critical.log"), "c\n");
+        assert_eq!(read(dir.path(), "critical.log.1"), "a\nb\n");
+        assert_eq!(read(dir.path(), "info.log"), "x\ny\n");
+        assert!(!dir.path().join("info.log.1").exists());
+    }
+}
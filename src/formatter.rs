@@ -29,3 +29,11 @@ pub fn format_line(original_line: &str, color_name: &str, icon: &str) -> String
     };
     format!("{} {}", icon, colored_line)
 }
+
+/// Formats a log line with its icon but without any color codes.
+///
+/// Used wherever ANSI escape sequences must not appear in the output, such as
+/// the JSON/JSONL `--output` modes and the plain-text `--save` archives.
+pub fn format_line_plain(original_line: &str, icon: &str) -> String {
+    format!("{} {}", icon, original_line)
+}
@@ -1,14 +1,22 @@
+mod export;
 mod formatter;
 mod parser;
+mod plugin;
 mod rules;
 
 use clap::Parser;
+use export::{DEFAULT_FILE_CAPACITY, ExportWriters};
+use formatter::{format_line, format_line_plain};
 use inquire::Select;
-use parser::parse_log;
-use rules::{LogCategory, RuleSet, load_rules_with_fallback};
+use parser::{ParsedLine, parse_log, parse_prefix};
+use plugin::Plugin;
+use rules::{CompiledRuleSet, LogCategory, RuleSet, load_rules_with_fallback};
+use serde::Serialize;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
 use tempfile::NamedTempFile;
 
 const EMBEDDED_DEFAULT_RULES: &str = include_str!("../rules/default_rules.toml");
@@ -25,14 +33,178 @@ const EMBEDDED_DEFAULT_RULES: &str = include_str!("../rules/default_rules.toml")
     long_about = "Reads kernel logs from dmesg or from a provided file and allows viewing categorized logs interactively."
 )]
 struct Cli {
-    /// Analyze a dmesg log file instead of reading the current kernel log
-    #[arg(short = 'f', long = "file", value_name = "FILE")]
+    /// Analyze a dmesg log file instead of reading the current kernel log.
+    /// Not compatible with --follow, which always reads the live kernel log.
+    #[arg(short = 'f', long = "file", value_name = "FILE", conflicts_with = "follow")]
     file: Option<String>,
 
     /// Path to a custom rule file (TOML format).
     /// If not provided, will search XDG config, /usr/share, or use embedded defaults.
     #[arg(short = 'R', long = "rules", value_name = "CUSTOM_RULES_PATH")]
     custom_rules_path: Option<String>,
+
+    /// Follow the live kernel log (`dmesg --follow`) and print matches as they arrive,
+    /// instead of reading a static snapshot into the interactive menu.
+    #[arg(short = 'w', long = "follow")]
+    follow: bool,
+
+    /// In follow mode, only print lines matching these categories (comma-separated).
+    #[arg(long = "only", value_delimiter = ',', requires = "follow")]
+    only: Option<Vec<LogCategory>>,
+
+    /// Output format: `interactive` shows the menu/pager, `json` and `jsonl`
+    /// print machine-readable records for scripting and pipelines.
+    #[arg(long = "output", value_enum, default_value = "interactive")]
+    output: OutputFormat,
+
+    /// Path to an external enrichment plugin (repeatable). Each plugin is run
+    /// as a child process and asked to classify lines over JSON-RPC; see `plugin.rs`.
+    #[arg(long = "plugin", value_name = "PATH")]
+    plugins: Vec<String>,
+
+    /// Only show lines whose dmesg subsystem tag matches one of these (comma-separated, e.g. `usb,nvme`).
+    #[arg(long = "tag", value_delimiter = ',')]
+    tag: Option<Vec<String>>,
+
+    /// Only show lines with a monotonic timestamp >= this many seconds.
+    #[arg(long = "since", value_name = "SECONDS")]
+    since: Option<f64>,
+
+    /// Only show lines with a monotonic timestamp <= this many seconds.
+    #[arg(long = "until", value_name = "SECONDS")]
+    until: Option<f64>,
+
+    /// Write each category's entries to its own size-rotated file in this directory
+    /// (e.g. `critical.log`, `error.log`), so they survive after the pager/menu exits.
+    #[arg(long = "save", value_name = "DIR")]
+    save: Option<PathBuf>,
+
+    /// Byte budget per saved log file before it rotates to a numbered rollover file.
+    #[arg(long = "save-capacity", value_name = "BYTES", default_value_t = DEFAULT_FILE_CAPACITY)]
+    save_capacity: u64,
+}
+
+/// Returns false if `parsed`'s tag or timestamp fails one of `cli`'s
+/// `--tag`/`--since`/`--until` filters. A line with no timestamp never
+/// passes `--since`/`--until`, since there is nothing to compare.
+fn passes_filters(parsed: &ParsedLine, cli: &Cli) -> bool {
+    if let Some(wanted_tags) = &cli.tag {
+        match &parsed.tag {
+            Some(tag) if wanted_tags.iter().any(|w| w.eq_ignore_ascii_case(tag)) => {}
+            _ => return false,
+        }
+    }
+
+    if let Some(since) = cli.since {
+        match parsed.timestamp {
+            Some(ts) if ts >= since => {}
+            _ => return false,
+        }
+    }
+
+    if let Some(until) = cli.until {
+        match parsed.timestamp {
+            Some(ts) if ts <= until => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// Output mode for categorized lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Launch the `inquire` menu and `less` pager (default).
+    Interactive,
+    /// Print every line as one JSON array to stdout (in `--follow` mode, one
+    /// JSON object per line instead, as there is no end to close an array at).
+    Json,
+    /// Print one JSON object per line to stdout, suitable for streaming.
+    Jsonl,
+}
+
+/// A single categorized line, serialized for `--output json`/`jsonl`.
+#[derive(Debug, Serialize)]
+struct JsonRecord {
+    category: LogCategory,
+    text: String,
+    raw: String,
+    line_no: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    annotation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+}
+
+/// The outcome of classifying one line: its category plus the color, icon,
+/// and optional annotation to format it with. Color/icon default to the
+/// matched rule's but a plugin may override either; `parse_log` itself never
+/// produces an annotation.
+struct Classification {
+    category: LogCategory,
+    color: String,
+    icon: String,
+    annotation: Option<String>,
+}
+
+/// Classifies `line` with the built-in rules, then gives every plugin whose
+/// declared categories include the built-in category a chance to override
+/// it, or its color, icon, or attach an annotation. Plugins that declared no
+/// categories (handshake failed, or they just didn't say) are asked
+/// regardless, as is every plugin when the built-in rules found no category
+/// at all. The first applicable plugin to respond wins; the rest are not
+/// consulted for this line. Returns None only if neither the built-in rules
+/// nor any plugin recognized the line.
+fn classify_line(
+    line: &str,
+    rules: &RuleSet,
+    compiled_rules: &CompiledRuleSet,
+    plugins: &mut [Plugin],
+) -> Option<Classification> {
+    let mut category = parse_log(line, compiled_rules);
+    let mut color = None;
+    let mut icon = None;
+    let mut annotation = None;
+
+    let response = plugins
+        .iter_mut()
+        .filter(|p| match category {
+            Some(cat) => p.categories().is_empty() || p.categories().contains(&cat),
+            None => true,
+        })
+        .find_map(|p| p.classify(line));
+
+    if let Some(response) = response {
+        if response.category.is_some() {
+            category = response.category;
+        }
+        color = response.color;
+        icon = response.icon;
+        annotation = response.annotation;
+    }
+
+    category.map(|category| {
+        let rule = rules.rule_for(category);
+        Classification {
+            category,
+            color: color.unwrap_or_else(|| rule.color.clone()),
+            icon: icon.unwrap_or_else(|| rule.icon.clone()),
+            annotation,
+        }
+    })
+}
+
+/// Appends `annotation` to `text` in parentheses, the same way the
+/// interactive view does, for sinks (the `--save` archive) that have no
+/// separate annotation field of their own to carry it in.
+fn with_annotation(text: &str, annotation: Option<&str>) -> String {
+    match annotation {
+        Some(annotation) => format!("{} ({})", text, annotation),
+        None => text.to_string(),
+    }
 }
 
 /// Entry point for the dmesg-analyzer application.
@@ -54,8 +226,88 @@ fn main() {
         );
     }
 
-    // Step 1: Open dmesg source (file or live)
-    let input: Box<dyn BufRead> = match cli.file {
+    let compiled_rules = CompiledRuleSet::compile(&ruleset_instance);
+
+    let mut plugins: Vec<Plugin> = cli
+        .plugins
+        .iter()
+        .filter_map(|path| match Plugin::spawn(path) {
+            Ok(plugin) => Some(plugin),
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                None
+            }
+        })
+        .collect();
+
+    let mut export_writers = open_export_writers(&cli);
+
+    if cli.follow {
+        run_follow_mode(
+            &cli,
+            &ruleset_instance,
+            &compiled_rules,
+            &mut plugins,
+            export_writers.as_mut(),
+        );
+        return;
+    }
+
+    // Step 1: Open dmesg source (file or a one-shot static snapshot).
+    let input = open_static_source(&cli);
+
+    if cli.output != OutputFormat::Interactive {
+        run_batch_output(
+            &cli,
+            input,
+            &ruleset_instance,
+            &compiled_rules,
+            &mut plugins,
+            export_writers.as_mut(),
+        );
+        return;
+    }
+
+    // Step 2: Parse lines into categorized buckets.
+    let (critical_lines, error_lines, warning_lines, info_lines) = collect_into_buckets(
+        input,
+        &cli,
+        &ruleset_instance,
+        &compiled_rules,
+        &mut plugins,
+        export_writers.as_mut(),
+    );
+
+    // Step 3: Show interactive selection menu.
+    loop {
+        display_menu(&critical_lines, &error_lines, &warning_lines, &info_lines);
+    }
+}
+
+/// Opens the `--save` export directory, if requested, and reports the chosen
+/// directory and rotation settings the same way the rules source is announced.
+fn open_export_writers(cli: &Cli) -> Option<ExportWriters> {
+    let dir = cli.save.as_ref()?;
+    match ExportWriters::open(dir, cli.save_capacity) {
+        Ok(writers) => {
+            println!(
+                "Saving categorized logs to: {} (rotating each file at {} bytes)",
+                dir.display(),
+                cli.save_capacity
+            );
+            Some(writers)
+        }
+        Err(e) => {
+            eprintln!("Error: Failed to open save directory '{}': {}", dir.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Opens a static (non-follow) dmesg source: either a user-provided file, or a
+/// one-shot `dmesg` run captured into a temporary file.
+fn open_static_source(cli: &Cli) -> Box<dyn BufRead> {
+    match cli.file {
         Some(ref path) => match File::open(path) {
             Ok(file) => Box::new(BufReader::new(file)),
             Err(e) => {
@@ -107,9 +359,20 @@ fn main() {
                 }
             }
         }
-    };
+    }
+}
 
-    // Step 2: Parse lines into categorized buckets.
+/// Reads every line from `input`, categorizes it, and collects the formatted
+/// lines into one bucket per severity. Used by the static (non-follow) path,
+/// which needs the whole log in memory before showing the interactive menu.
+fn collect_into_buckets(
+    input: Box<dyn BufRead>,
+    cli: &Cli,
+    rules: &RuleSet,
+    compiled_rules: &CompiledRuleSet,
+    plugins: &mut [Plugin],
+    mut export_writers: Option<&mut ExportWriters>,
+) -> (Vec<String>, Vec<String>, Vec<String>, Vec<String>) {
     let mut critical_lines = Vec::new();
     let mut error_lines = Vec::new();
     let mut warning_lines = Vec::new();
@@ -124,22 +387,220 @@ fn main() {
             }
         };
 
-        if let Some((formatted_string, category)) = parse_log(&line, &ruleset_instance) {
-            match category {
+        if !passes_filters(&parse_prefix(&line), cli) {
+            continue;
+        }
+
+        if let Some(classification) = classify_line(&line, rules, compiled_rules, plugins) {
+            if let Some(writers) = export_writers.as_deref_mut() {
+                let plain_text = format_line_plain(&line, &classification.icon);
+                let export_text = with_annotation(&plain_text, classification.annotation.as_deref());
+                if let Err(e) = writers.write(classification.category, &export_text) {
+                    eprintln!("Warning: Failed to save log line: {}", e);
+                }
+            }
+
+            let mut formatted_string = format_line(&line, &classification.color, &classification.icon);
+            if let Some(annotation) = &classification.annotation {
+                formatted_string = format!("{} ({})", formatted_string, annotation);
+            }
+            match classification.category {
                 LogCategory::Critical => critical_lines.push(formatted_string),
                 LogCategory::Error => error_lines.push(formatted_string),
                 LogCategory::Warning => warning_lines.push(formatted_string),
                 LogCategory::Info => info_lines.push(formatted_string),
             }
         } else {
-            // If the line does not match any rule, add it as-is to info_lines.
+            // If the line does not match any rule or plugin, add it as-is to info_lines.
             info_lines.push(line);
         }
     }
 
-    // Step 3: Show interactive selection menu.
-    loop {
-        display_menu(&critical_lines, &error_lines, &warning_lines, &info_lines);
+    (critical_lines, error_lines, warning_lines, info_lines)
+}
+
+/// Reads every line from `input`, categorizes it, and prints a JSON (or
+/// JSONL) record per line instead of showing the interactive menu. `json`
+/// buffers every record and prints one array; `jsonl` prints one object per
+/// line as it is produced.
+fn run_batch_output(
+    cli: &Cli,
+    input: Box<dyn BufRead>,
+    rules: &RuleSet,
+    compiled_rules: &CompiledRuleSet,
+    plugins: &mut [Plugin],
+    mut export_writers: Option<&mut ExportWriters>,
+) {
+    let mut records = Vec::new();
+
+    for (line_no, line_result) in input.lines().enumerate() {
+        let line = match line_result {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("Warning: Skipping line due to read error: {}", e);
+                continue;
+            }
+        };
+
+        let parsed = parse_prefix(&line);
+        if !passes_filters(&parsed, cli) {
+            continue;
+        }
+
+        let classification = classify_line(&line, rules, compiled_rules, plugins).unwrap_or_else(|| {
+            let rule = rules.rule_for(LogCategory::Info);
+            Classification {
+                category: LogCategory::Info,
+                color: rule.color.clone(),
+                icon: rule.icon.clone(),
+                annotation: None,
+            }
+        });
+        let plain_text = format_line_plain(&line, &classification.icon);
+        if let Some(writers) = export_writers.as_deref_mut() {
+            let export_text = with_annotation(&plain_text, classification.annotation.as_deref());
+            if let Err(e) = writers.write(classification.category, &export_text) {
+                eprintln!("Warning: Failed to save log line: {}", e);
+            }
+        }
+
+        let record = JsonRecord {
+            category: classification.category,
+            text: plain_text,
+            raw: line,
+            line_no: line_no + 1,
+            annotation: classification.annotation,
+            timestamp: parsed.timestamp,
+            tag: parsed.tag,
+        };
+
+        match cli.output {
+            OutputFormat::Jsonl => match serde_json::to_string(&record) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("Warning: Failed to serialize record: {}", e),
+            },
+            OutputFormat::Json => records.push(record),
+            OutputFormat::Interactive => unreachable!("run_batch_output is only called for json/jsonl output"),
+        }
+    }
+
+    if cli.output == OutputFormat::Json {
+        match serde_json::to_string_pretty(&records) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error: Failed to serialize records: {}", e),
+        }
+    }
+}
+
+/// Spawns `dmesg --follow`, categorizes each line as it arrives, and prints it
+/// immediately instead of collecting into buckets for the interactive menu.
+///
+/// Honors `cli.only` as a severity filter and kills the child `dmesg` process
+/// on Ctrl-C so the stream stops cleanly instead of leaking a background process.
+fn run_follow_mode(
+    cli: &Cli,
+    rules: &RuleSet,
+    compiled_rules: &CompiledRuleSet,
+    plugins: &mut [Plugin],
+    mut export_writers: Option<&mut ExportWriters>,
+) {
+    let mut child = match Command::new("dmesg")
+        .arg("--follow")
+        .stdout(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Error: Failed to spawn 'dmesg --follow': {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let stdout = child
+        .stdout
+        .take()
+        .expect("dmesg child process was spawned without a piped stdout");
+
+    let child = Arc::new(Mutex::new(child));
+    let ctrlc_child = Arc::clone(&child);
+    if let Err(e) = ctrlc::set_handler(move || {
+        if let Ok(mut child) = ctrlc_child.lock() {
+            let _ = child.kill();
+        }
+        std::process::exit(0);
+    }) {
+        eprintln!("Warning: Failed to install Ctrl-C handler: {}", e);
+    }
+
+    let reader = BufReader::new(stdout);
+    for (line_no, line_result) in reader.lines().enumerate() {
+        let line = match line_result {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("Warning: Skipping line due to read error: {}", e);
+                continue;
+            }
+        };
+
+        let parsed = parse_prefix(&line);
+        if !passes_filters(&parsed, cli) {
+            continue;
+        }
+
+        let classification = classify_line(&line, rules, compiled_rules, plugins).unwrap_or_else(|| {
+            let rule = rules.rule_for(LogCategory::Info);
+            Classification {
+                category: LogCategory::Info,
+                color: rule.color.clone(),
+                icon: rule.icon.clone(),
+                annotation: None,
+            }
+        });
+
+        if let Some(only) = &cli.only {
+            if !only.contains(&classification.category) {
+                continue;
+            }
+        }
+
+        let plain_text = format_line_plain(&line, &classification.icon);
+        if let Some(writers) = export_writers.as_deref_mut() {
+            let export_text = with_annotation(&plain_text, classification.annotation.as_deref());
+            if let Err(e) = writers.write(classification.category, &export_text) {
+                eprintln!("Warning: Failed to save log line: {}", e);
+            }
+        }
+
+        match cli.output {
+            OutputFormat::Interactive => {
+                let mut formatted_string = format_line(&line, &classification.color, &classification.icon);
+                if let Some(annotation) = &classification.annotation {
+                    formatted_string = format!("{} ({})", formatted_string, annotation);
+                }
+                println!("{}", formatted_string);
+            }
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                // A live stream has no natural end, so both JSON modes print
+                // one object per line rather than buffering into an array.
+                let record = JsonRecord {
+                    category: classification.category,
+                    text: plain_text,
+                    raw: line,
+                    line_no: line_no + 1,
+                    annotation: classification.annotation,
+                    timestamp: parsed.timestamp,
+                    tag: parsed.tag,
+                };
+                match serde_json::to_string(&record) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => eprintln!("Warning: Failed to serialize record: {}", e),
+                }
+            }
+        }
+    }
+
+    if let Ok(mut child) = child.lock() {
+        let _ = child.wait();
     }
 }
 
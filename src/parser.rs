@@ -1,46 +1,186 @@
 // src/parser.rs
-use crate::formatter::format_line;
-use crate::rules::{LogCategory, Rule, RuleSet};
+use crate::rules::{CompiledRuleSet, LogCategory};
+use regex::Regex;
+use std::sync::OnceLock;
 
-/// Parses a log line and categorizes it according to the provided rules.
+/// Classifies a log line against the precompiled rule sets.
 ///
-/// Returns a tuple of the formatted string and its log category if a rule matches, or None otherwise.
-pub fn parse_log(line: &str, rules: &RuleSet) -> Option<(String, LogCategory)> {
+/// Returns the matched `LogCategory`, or None if no rule matched. Formatting
+/// (color, icon) is deliberately left to the caller, since the same
+/// classification feeds both the colored interactive view and the plain
+/// JSON/JSONL output.
+pub fn parse_log(line: &str, compiled: &CompiledRuleSet) -> Option<LogCategory> {
     // The order of checks determines priority: critical > error > warning > info.
-    if matches_rule(line, &rules.critical) {
-        Some((
-            format_line(line, &rules.critical.color, &rules.critical.icon),
-            LogCategory::Critical,
-        ))
-    } else if matches_rule(line, &rules.error) {
-        Some((
-            format_line(line, &rules.error.color, &rules.error.icon),
-            LogCategory::Error,
-        ))
-    } else if matches_rule(line, &rules.warning) {
-        Some((
-            format_line(line, &rules.warning.color, &rules.warning.icon),
-            LogCategory::Warning,
-        ))
-    } else if matches_rule(line, &rules.info) {
-        // Optionally match info-specific keywords.
-        Some((
-            format_line(line, &rules.info.color, &rules.info.icon),
-            LogCategory::Info,
-        ))
+    if compiled.critical.is_match(line) {
+        Some(LogCategory::Critical)
+    } else if compiled.error.is_match(line) {
+        Some(LogCategory::Error)
+    } else if compiled.warning.is_match(line) {
+        Some(LogCategory::Warning)
+    } else if compiled.info.is_match(line) {
+        // Optionally match info-specific keywords/patterns.
+        Some(LogCategory::Info)
     } else {
         // No rule matched; let the caller decide how to handle the line.
         None
     }
 }
 
-/// Checks if a log line matches any of the keywords in the given rule (case-insensitive).
-fn matches_rule(line: &str, rule: &Rule) -> bool {
-    // If no keywords are defined, this rule never matches.
-    if rule.keywords.is_empty() {
-        return false;
+/// The structured fields dmesg prefixes a log line with.
+///
+/// Both fields are None when the line has no recognizable prefix (or, for
+/// `timestamp`, when it uses the human `--time-format` form, which carries
+/// no monotonic seconds to filter on). The line itself is left untouched by
+/// `parse_prefix` — callers that need the prefix stripped for display should
+/// slice it out themselves; these fields are consumed by `passes_filters` and
+/// carried into the JSON/JSONL output records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedLine {
+    pub timestamp: Option<f64>,
+    pub tag: Option<String>,
+}
+
+fn monotonic_timestamp_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^\[\s*(?P<ts>\d+\.\d+)\]\s*(?P<rest>.*)$").expect("monotonic dmesg prefix regex is valid")
+    })
+}
+
+fn human_timestamp_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // e.g. "[Mon Jan  2 15:04:05 2006]", as produced by `dmesg --time-format=ctime`.
+        Regex::new(r"^\[[A-Za-z]{3} [A-Za-z]{3} +\d+ \d+:\d+:\d+ \d+\]\s*(?P<rest>.*)$")
+            .expect("human dmesg prefix regex is valid")
+    })
+}
+
+/// Matches a leading `subsystem:` facility tag, optionally followed by a
+/// single unit/device token before the colon — e.g. `usb 1-1:`, `nvme nvme0:`,
+/// `EXT4-fs (sda1):`, or a bare `CPU:`. Requires an actual colon so that an
+/// ordinary sentence's first word (e.g. "Linux version 5.15.0 ...") is not
+/// mistaken for a tag.
+fn tag_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^(?P<tag>[A-Za-z][A-Za-z0-9_-]*)(?:[ \t]+[^\s:]+)?:").expect("subsystem tag regex is valid")
+    })
+}
+
+/// Extracts a leading `subsystem:` tag (see `tag_re`) from `rest`, if any.
+fn extract_tag(rest: &str) -> Option<String> {
+    tag_re().captures(rest).and_then(|caps| caps.name("tag").map(|m| m.as_str().to_string()))
+}
+
+/// Recognizes the standard dmesg prefix — either the bracketed monotonic
+/// `[ 12.345678]` timestamp or the human `--time-format` form — plus a
+/// trailing `subsystem:` facility tag, and extracts them from `line`.
+pub fn parse_prefix(line: &str) -> ParsedLine {
+    if let Some(caps) = monotonic_timestamp_re().captures(line) {
+        let timestamp = caps.name("ts").and_then(|m| m.as_str().parse().ok());
+        let rest = caps.name("rest").map_or("", |m| m.as_str());
+        return ParsedLine {
+            timestamp,
+            tag: extract_tag(rest),
+        };
+    }
+
+    if let Some(caps) = human_timestamp_re().captures(line) {
+        let rest = caps.name("rest").map_or("", |m| m.as_str());
+        return ParsedLine {
+            timestamp: None,
+            tag: extract_tag(rest),
+        };
+    }
+
+    // No dmesg-style timestamp prefix; still look for a bare `tag: ` facility prefix.
+    ParsedLine {
+        timestamp: None,
+        tag: extract_tag(line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Rule, RuleSet};
+
+    fn rule(keyword: &str) -> Rule {
+        Rule {
+            keywords: vec![keyword.to_string()],
+            patterns: vec![],
+            color: "red".to_string(),
+            icon: "X".to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_log_prioritizes_critical_over_error_over_warning_over_info() {
+        // A line matching every category's rule must still resolve to the
+        // most severe one, regardless of how each rule is internally compiled.
+        let rules = RuleSet {
+            critical: rule("panic"),
+            error: rule("panic"),
+            warning: rule("panic"),
+            info: rule("panic"),
+        };
+        let compiled = CompiledRuleSet::compile(&rules);
+        assert_eq!(parse_log("kernel panic", &compiled), Some(LogCategory::Critical));
+
+        let rules = RuleSet {
+            critical: rule("does-not-appear"),
+            error: rule("oops"),
+            warning: rule("oops"),
+            info: rule("oops"),
+        };
+        let compiled = CompiledRuleSet::compile(&rules);
+        assert_eq!(parse_log("an oops occurred", &compiled), Some(LogCategory::Error));
+    }
+
+    #[test]
+    fn parses_monotonic_timestamp_and_simple_tag() {
+        let parsed = parse_prefix("[    1.234567] usb: USB hub found");
+        assert_eq!(parsed.timestamp, Some(1.234567));
+        assert_eq!(parsed.tag.as_deref(), Some("usb"));
+    }
+
+    #[test]
+    fn parses_tag_with_leading_unit_id() {
+        let parsed = parse_prefix("[   12.345678] usb 1-1: new high-speed USB device number 3 using xhci_hcd");
+        assert_eq!(parsed.timestamp, Some(12.345678));
+        assert_eq!(parsed.tag.as_deref(), Some("usb"));
+    }
+
+    #[test]
+    fn parses_nvme_tag_with_unit_id() {
+        let parsed = parse_prefix("[   30.000001] nvme nvme0: 16/0/0 default/read/poll queues");
+        assert_eq!(parsed.tag.as_deref(), Some("nvme"));
+    }
+
+    #[test]
+    fn parses_ext4_tag_with_parenthesized_unit_id() {
+        let parsed = parse_prefix("[   45.000001] EXT4-fs (sda1): mounted filesystem with ordered data mode");
+        assert_eq!(parsed.tag.as_deref(), Some("EXT4-fs"));
+    }
+
+    #[test]
+    fn human_time_format_has_no_timestamp_but_keeps_tag() {
+        let parsed = parse_prefix("[Mon Jan  2 15:04:05 2006] usb 1-1: new high-speed USB device number 3 using xhci_hcd");
+        assert_eq!(parsed.timestamp, None);
+        assert_eq!(parsed.tag.as_deref(), Some("usb"));
+    }
+
+    #[test]
+    fn plain_sentence_is_not_mistaken_for_a_tag() {
+        let parsed = parse_prefix("[    0.000000] Linux version 5.15.0 (gcc version 9.3.0) #1 SMP");
+        assert_eq!(parsed.tag, None);
+    }
+
+    #[test]
+    fn line_with_no_prefix_at_all() {
+        let parsed = parse_prefix("healthy boot continuing");
+        assert_eq!(parsed.timestamp, None);
+        assert_eq!(parsed.tag, None);
     }
-    rule.keywords
-        .iter()
-        .any(|kw| line.to_lowercase().contains(&kw.to_lowercase()))
 }
@@ -0,0 +1,185 @@
+// src/plugin.rs
+use crate::rules::LogCategory;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for a plugin to answer a single request before falling
+/// back to the built-in rules.
+pub const PLUGIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A plugin's answer to the `signature` handshake: its name and which
+/// categories it may emit.
+#[derive(Debug, Deserialize, Default)]
+struct Signature {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    categories: Vec<LogCategory>,
+}
+
+/// A plugin's answer to a `classify` request.
+///
+/// Any field left absent falls back to the built-in rule for the chosen
+/// category (or, for `category`, to whatever `parse_log` already decided).
+#[derive(Debug, Deserialize, Default)]
+pub struct ClassifyResponse {
+    pub category: Option<LogCategory>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub annotation: Option<String>,
+}
+
+/// An external enrichment plugin: a child process speaking line-delimited
+/// JSON-RPC over stdin/stdout, modeled on nushell's plugin protocol.
+///
+/// Responses are read off a background thread into a channel so a slow or
+/// hung plugin can be abandoned with a timeout instead of blocking the
+/// analyzer's parse loop.
+pub struct Plugin {
+    path: String,
+    name: String,
+    categories: Vec<LogCategory>,
+    child: Child,
+    stdin: ChildStdin,
+    responses: Receiver<String>,
+}
+
+impl Plugin {
+    /// The categories this plugin declared it can emit during the `signature`
+    /// handshake. Empty means the plugin didn't declare any (either the
+    /// handshake failed, or the plugin just didn't say) — callers should
+    /// treat that as "ask for every category" rather than "ask for none".
+    pub fn categories(&self) -> &[LogCategory] {
+        &self.categories
+    }
+
+    /// Spawns `path` as a plugin and performs the `signature` handshake.
+    ///
+    /// Returns an error (rather than panicking) if the process cannot be
+    /// spawned; a plugin that fails or times out during the handshake still
+    /// starts, under its path as its name, so a flaky handshake does not
+    /// stop `classify` calls from being attempted later.
+    pub fn spawn(path: &str) -> Result<Self, String> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("failed to spawn plugin '{}': {}", path, e))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .expect("plugin child process was spawned without a piped stdin");
+        let stdout = child
+            .stdout
+            .take()
+            .expect("plugin child process was spawned without a piped stdout");
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let reader = BufReader::new(stdout);
+            for line in reader.lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut plugin = Plugin {
+            path: path.to_string(),
+            name: path.to_string(),
+            categories: Vec::new(),
+            child,
+            stdin,
+            responses: rx,
+        };
+
+        if let Err(e) = plugin.send(&json!({"method": "signature"})) {
+            eprintln!("Warning: Plugin '{}' handshake failed: {}", path, e);
+            return Ok(plugin);
+        }
+
+        match plugin.responses.recv_timeout(PLUGIN_TIMEOUT) {
+            Ok(line) => match serde_json::from_str::<Signature>(&line) {
+                Ok(sig) => {
+                    if let Some(name) = sig.name {
+                        plugin.name = name;
+                    }
+                    plugin.categories = sig.categories;
+                }
+                Err(e) => eprintln!(
+                    "Warning: Plugin '{}' sent an invalid signature response: {}",
+                    path, e
+                ),
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                eprintln!("Warning: Plugin '{}' did not answer the signature handshake in time.", path);
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                eprintln!("Warning: Plugin '{}' exited before completing the handshake.", path);
+            }
+        }
+
+        Ok(plugin)
+    }
+
+    /// Asks the plugin to classify `line`. Returns None (and logs a warning)
+    /// if the plugin errors or does not answer within `PLUGIN_TIMEOUT`, so
+    /// the caller can fall back to the built-in rules.
+    pub fn classify(&mut self, line: &str) -> Option<ClassifyResponse> {
+        // A previous call's response can still be in flight after we gave up
+        // on it at its timeout; drain any such stale replies first so they
+        // aren't mistaken for the answer to *this* line.
+        while self.responses.try_recv().is_ok() {}
+
+        if let Err(e) = self.send(&json!({"method": "classify", "params": {"line": line}})) {
+            eprintln!("Warning: Plugin '{}' write failed, falling back to built-in rules: {}", self.name, e);
+            return None;
+        }
+
+        match self.responses.recv_timeout(PLUGIN_TIMEOUT) {
+            Ok(response_line) => match serde_json::from_str(&response_line) {
+                Ok(response) => Some(response),
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Plugin '{}' sent an invalid classify response, falling back: {}",
+                        self.name, e
+                    );
+                    None
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                eprintln!("Warning: Plugin '{}' timed out, falling back to built-in rules.", self.name);
+                None
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                eprintln!("Warning: Plugin '{}' exited, falling back to built-in rules.", self.name);
+                None
+            }
+        }
+    }
+
+    fn send(&mut self, request: &serde_json::Value) -> Result<(), String> {
+        let line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        writeln!(self.stdin, "{}", line).map_err(|e| e.to_string())?;
+        self.stdin.flush().map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        if let Err(e) = self.child.kill() {
+            // Already exited is not worth a warning; anything else is.
+            if e.kind() != std::io::ErrorKind::InvalidInput {
+                eprintln!("Warning: Failed to stop plugin '{}': {}", self.path, e);
+            }
+        }
+        let _ = self.child.wait();
+    }
+}
@@ -1,9 +1,11 @@
-use serde::Deserialize;
+use regex::{escape, RegexSet, RegexSetBuilder};
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::path::Path;
 
 /// Represents the log category for a parsed log line.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogCategory {
     /// Critical severity log
     Critical,
@@ -28,17 +30,158 @@ pub struct RuleSet {
     pub info: Rule,
 }
 
+impl RuleSet {
+    /// Returns the `Rule` (color, icon, keywords/patterns) for a given category.
+    pub fn rule_for(&self, category: LogCategory) -> &Rule {
+        match category {
+            LogCategory::Critical => &self.critical,
+            LogCategory::Error => &self.error,
+            LogCategory::Warning => &self.warning,
+            LogCategory::Info => &self.info,
+        }
+    }
+}
+
 /// A rule for matching log lines, including keywords, color, and icon.
 #[derive(Debug, Deserialize)]
 pub struct Rule {
     /// Keywords that trigger this rule
     pub keywords: Vec<String>,
+    /// Regex patterns that trigger this rule, in addition to `keywords`
+    #[serde(default)]
+    pub patterns: Vec<String>,
     /// Color name for highlighting
     pub color: String,
     /// Icon to display with the log
     pub icon: String,
 }
 
+/// A single rule's keywords and patterns compiled into one `RegexSet`.
+///
+/// Compiling once at load time lets `parse_log` test every keyword and
+/// pattern for a category in a single pass over the line, instead of
+/// lowercasing and scanning the line once per keyword.
+#[derive(Debug)]
+pub struct CompiledRule {
+    set: RegexSet,
+}
+
+impl CompiledRule {
+    /// Returns true if the line matches any keyword or pattern in this rule.
+    pub fn is_match(&self, line: &str) -> bool {
+        self.set.is_match(line)
+    }
+
+    /// Compiles a `Rule`'s keywords and patterns into a single `RegexSet`.
+    ///
+    /// Keywords are escaped into literal regexes so they keep matching as
+    /// plain substrings. Invalid user-supplied patterns are skipped with a
+    /// warning rather than causing a panic.
+    fn compile(rule: &Rule) -> Self {
+        let mut fragments = Vec::with_capacity(rule.keywords.len() + rule.patterns.len());
+        fragments.extend(rule.keywords.iter().map(|kw| escape(kw)));
+
+        for pattern in &rule.patterns {
+            if regex::Regex::new(pattern).is_ok() {
+                fragments.push(pattern.clone());
+            } else {
+                eprintln!(
+                    "Warning: Skipping invalid rule pattern '{}': not a valid regex.",
+                    pattern
+                );
+            }
+        }
+
+        let set = RegexSetBuilder::new(&fragments)
+            .case_insensitive(true)
+            .build()
+            .unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to build regex set, rule will never match: {}", e);
+                RegexSet::empty()
+            });
+
+        CompiledRule { set }
+    }
+}
+
+/// A `RuleSet` with every category's keywords and patterns precompiled into
+/// a `RegexSet`, built once at load time and reused for every line.
+#[derive(Debug)]
+pub struct CompiledRuleSet {
+    pub critical: CompiledRule,
+    pub error: CompiledRule,
+    pub warning: CompiledRule,
+    pub info: CompiledRule,
+}
+
+impl CompiledRuleSet {
+    /// Compiles every category in `rules` into its own `RegexSet`.
+    pub fn compile(rules: &RuleSet) -> Self {
+        CompiledRuleSet {
+            critical: CompiledRule::compile(&rules.critical),
+            error: CompiledRule::compile(&rules.error),
+            warning: CompiledRule::compile(&rules.warning),
+            info: CompiledRule::compile(&rules.info),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(keywords: &[&str], patterns: &[&str]) -> Rule {
+        Rule {
+            keywords: keywords.iter().map(|s| s.to_string()).collect(),
+            patterns: patterns.iter().map(|s| s.to_string()).collect(),
+            color: "red".to_string(),
+            icon: "X".to_string(),
+        }
+    }
+
+    #[test]
+    fn invalid_pattern_is_skipped_without_poisoning_the_rest() {
+        let compiled = CompiledRule::compile(&rule(&["foo"], &["(unterminated", "bar.*baz"]));
+        assert!(compiled.is_match("a foo line"));
+        assert!(compiled.is_match("bar and baz"));
+    }
+
+    #[test]
+    fn all_patterns_invalid_never_panics_and_never_matches() {
+        let compiled = CompiledRule::compile(&rule(&[], &["(", "*bad"]));
+        assert!(!compiled.is_match("anything at all"));
+    }
+
+    #[test]
+    fn keywords_are_escaped_literals_matched_case_insensitively() {
+        let compiled = CompiledRule::compile(&rule(&["kernel.panic"], &[]));
+        assert!(compiled.is_match("KERNEL.PANIC detected"));
+        // The '.' in the keyword must not act as a regex wildcard.
+        assert!(!compiled.is_match("kernelXpanic"));
+    }
+
+    fn ruleset_where_every_category_matches_the_same_keyword(keyword: &str) -> RuleSet {
+        RuleSet {
+            critical: rule(&[keyword], &[]),
+            error: rule(&[keyword], &[]),
+            warning: rule(&[keyword], &[]),
+            info: rule(&[keyword], &[]),
+        }
+    }
+
+    #[test]
+    fn compiled_rule_set_compiles_every_category_to_match_a_shared_keyword() {
+        // parse_log (src/parser.rs) checks critical, then error, then warning,
+        // then info; this only confirms the rewritten RegexSet-backed
+        // CompiledRule still matches so that ordering has something to work with.
+        let compiled = CompiledRuleSet::compile(&ruleset_where_every_category_matches_the_same_keyword("panic"));
+        assert!(compiled.critical.is_match("kernel panic"));
+        assert!(compiled.error.is_match("kernel panic"));
+        assert!(compiled.warning.is_match("kernel panic"));
+        assert!(compiled.info.is_match("kernel panic"));
+    }
+}
+
 /// Loads rules from a custom path, XDG config, /usr/share, or falls back to embedded defaults.
 ///
 /// Returns a tuple of the loaded RuleSet and a string describing the source.